@@ -1,12 +1,214 @@
-use std::process::{Child, Command};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
 use std::net::TcpListener;
-use std::time::Duration;
-use std::thread;
-use tauri::Manager;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+use tauri::{Emitter, Manager};
+
+/// How long to wait for the sidecar to exit cleanly before forcing a kill.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+/// Rotate `backend.log` once it grows past this size, like a desktop client.
+const LOG_MAX_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated log files to keep (`backend.log.1` .. `backend.log.N`).
+const LOG_MAX_FILES: usize = 5;
+
+/// Tunables for the startup readiness probe.
+///
+/// Polling backs off exponentially from `initial_interval` up to `max_interval`
+/// and gives up once `timeout` elapses, so a slow first boot gets time while a
+/// crashed child is caught by the liveness check rather than waiting it out.
+#[derive(Clone)]
+pub struct ReadinessConfig {
+    /// HTTP path probed for readiness (expected to return 2xx when ready).
+    pub health_path: String,
+    /// First poll interval; doubles each attempt up to `max_interval`.
+    pub initial_interval: Duration,
+    /// Upper bound on the poll interval.
+    pub max_interval: Duration,
+    /// Overall deadline before the probe reports a timeout.
+    pub timeout: Duration,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        ReadinessConfig {
+            health_path: "/api/health".to_string(),
+            initial_interval: Duration::from_millis(250),
+            max_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// Why the backend never became ready during startup.
+#[derive(Debug)]
+pub enum BackendStartError {
+    /// The readiness deadline elapsed without a ready response.
+    Timeout,
+    /// The child process exited before it became ready.
+    ProcessExited,
+    /// The port never accepted a connection (backend not listening yet).
+    ConnectionRefused,
+}
+
+impl std::fmt::Display for BackendStartError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackendStartError::Timeout => {
+                write!(f, "Backend failed to become ready within the timeout")
+            }
+            BackendStartError::ProcessExited => {
+                write!(f, "Backend process exited before becoming ready")
+            }
+            BackendStartError::ConnectionRefused => {
+                write!(f, "Backend never accepted a connection")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BackendStartError {}
+
+/// Size-rotated line log shared by the stdout/stderr reader threads.
+struct RotatingLog {
+    dir: PathBuf,
+    file: Option<File>,
+    written: u64,
+}
+
+impl RotatingLog {
+    fn new(dir: PathBuf) -> Self {
+        RotatingLog {
+            dir,
+            file: None,
+            written: 0,
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join("backend.log")
+    }
+
+    /// Open (appending) the active log file. An existing `backend.log` is
+    /// continued, not rotated; rotation happens only in `write_line` by size.
+    fn ensure_open(&mut self) -> std::io::Result<()> {
+        if self.file.is_some() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&self.dir)?;
+        let path = self.path();
+        let meta = std::fs::metadata(&path).ok();
+        self.written = meta.as_ref().map(|m| m.len()).unwrap_or(0);
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        self.file = Some(file);
+        Ok(())
+    }
+
+    /// Shift `backend.log` to `backend.log.1`, ageing out the oldest file.
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file = None;
+        let oldest = self.dir.join(format!("backend.log.{}", LOG_MAX_FILES));
+        let _ = std::fs::remove_file(&oldest);
+        for idx in (1..LOG_MAX_FILES).rev() {
+            let from = self.dir.join(format!("backend.log.{}", idx));
+            let to = self.dir.join(format!("backend.log.{}", idx + 1));
+            if from.exists() {
+                let _ = std::fs::rename(&from, &to);
+            }
+        }
+        let current = self.path();
+        if current.exists() {
+            std::fs::rename(&current, self.dir.join("backend.log.1"))?;
+        }
+        self.written = 0;
+        self.ensure_open()
+    }
+
+    fn write_line(&mut self, stream: &str, line: &str) {
+        if self.ensure_open().is_err() {
+            return;
+        }
+        if self.written >= LOG_MAX_BYTES && self.rotate().is_err() {
+            return;
+        }
+        if let Some(file) = self.file.as_mut() {
+            let record = format!("[{}] {}\n", stream, line);
+            if file.write_all(record.as_bytes()).is_ok() {
+                self.written += record.len() as u64;
+            }
+        }
+    }
+
+    /// Return the last `lines` lines, walking the active log then the rotated
+    /// files newest-to-oldest until enough lines are gathered.
+    fn tail(&self, lines: usize) -> Vec<String> {
+        let mut collected: Vec<String> = Vec::new();
+        let mut paths = vec![self.path()];
+        for idx in 1..=LOG_MAX_FILES {
+            paths.push(self.dir.join(format!("backend.log.{}", idx)));
+        }
+        // Walk newest-to-oldest, prepending each older file's lines, until we
+        // have enough lines.
+        for path in &paths {
+            let Ok(file) = File::open(path) else { continue };
+            let file_lines: Vec<String> = BufReader::new(file)
+                .lines()
+                .map_while(Result::ok)
+                .collect();
+            let mut merged = file_lines;
+            merged.extend(collected);
+            collected = merged;
+            if collected.len() >= lines {
+                break;
+            }
+        }
+        let start = collected.len().saturating_sub(lines);
+        collected.split_off(start)
+    }
+}
+
+/// Which managed data directories `reset_app_data` should wipe.
+///
+/// The sidecar seeds these stores under `app_data_dir` via env vars, so clearing
+/// them returns the app to a first-run state for troubleshooting or re-onboarding.
+#[derive(serde::Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ResetScope {
+    /// The SQLite chat history (`chats.db`).
+    Chats,
+    /// The LanceDB vector store (`memory`).
+    Memory,
+    /// Sandbox working data (`sandbox-data`).
+    Sandbox,
+    /// Installed skills (`skills`).
+    Skills,
+    /// Every managed store.
+    All,
+}
 
 pub struct BackendProcess {
     child: Option<Child>,
     pub port: u16,
+    log: Arc<Mutex<RotatingLog>>,
+    readers: Vec<JoinHandle<()>>,
+    /// Set once an intentional stop begins so the supervisor stops restarting.
+    shutting_down: Arc<AtomicBool>,
+    /// Loopback control channel (port, secret) the sidecar uses to drive the
+    /// window. Passed through as env vars on every spawn, including restarts.
+    control: Option<(u16, String)>,
+    /// CLI override for the listen port; bypasses `find_available_port()`.
+    port_override: Option<u16>,
+    /// Host the backend binds to (CLI `--host`, default loopback).
+    host: String,
+    /// CLI override for the app data directory; replaces `app_data_dir`.
+    data_dir_override: Option<PathBuf>,
+    /// Startup readiness-probe tunables.
+    readiness: ReadinessConfig,
 }
 
 impl BackendProcess {
@@ -14,6 +216,60 @@ impl BackendProcess {
         BackendProcess {
             child: None,
             port: 0,
+            // Real directory is set in `start`; the placeholder keeps `new()` infallible.
+            log: Arc::new(Mutex::new(RotatingLog::new(PathBuf::new()))),
+            readers: Vec::new(),
+            shutting_down: Arc::new(AtomicBool::new(false)),
+            control: None,
+            port_override: None,
+            host: "127.0.0.1".to_string(),
+            data_dir_override: None,
+            readiness: ReadinessConfig::default(),
+        }
+    }
+
+    /// Override the startup readiness-probe tunables (health path, backoff,
+    /// timeout). Applies to the next `start`/`restart`.
+    pub fn set_readiness_config(&mut self, config: ReadinessConfig) {
+        self.readiness = config;
+    }
+
+    /// Register the reverse-control channel so the sidecar can reach back into
+    /// the GUI. Must be set before `start`; the values are re-applied on restart.
+    pub fn set_control_channel(&mut self, port: u16, token: String) {
+        self.control = Some((port, token));
+    }
+
+    /// Apply CLI overrides before `start`. A fixed `port` bypasses port discovery
+    /// (readiness is still polled), `host` sets the bind address, and `data_dir`
+    /// replaces `app_data_dir` when composing the backend's env vars.
+    pub fn set_launch_options(
+        &mut self,
+        port: Option<u16>,
+        host: Option<String>,
+        data_dir: Option<PathBuf>,
+    ) {
+        self.port_override = port;
+        if let Some(host) = host {
+            self.host = host;
+        }
+        self.data_dir_override = data_dir;
+    }
+
+    /// Resolve the base directory for the backend's stores, honoring a CLI
+    /// `--data-dir` override and otherwise falling back to the platform app-data
+    /// dir. Used by both `spawn_backend` and `reset_data` so they agree on where
+    /// the stores actually live.
+    fn resolve_app_data_dir(
+        &self,
+        app_handle: &tauri::AppHandle,
+    ) -> Result<PathBuf, String> {
+        match &self.data_dir_override {
+            Some(dir) => Ok(dir.clone()),
+            None => app_handle
+                .path()
+                .app_data_dir()
+                .map_err(|e| format!("Failed to get app data dir: {}", e)),
         }
     }
 
@@ -44,64 +300,306 @@ impl BackendProcess {
     /// Only called in release builds - in debug mode the backend runs separately.
     #[allow(dead_code)] // Only used in release builds via cfg
     pub fn start(&mut self, app_handle: &tauri::AppHandle) -> Result<u16, String> {
-        let port = Self::find_available_port()?;
+        self.spawn_backend(app_handle)
+    }
+
+    /// Re-spawn the sidecar after an unexpected exit, allocating a fresh port
+    /// (or reusing the fixed `--port` override when one is set).
+    /// Called by the supervisor; reuses the same env/log wiring as `start`.
+    pub fn restart(&mut self, app_handle: &tauri::AppHandle) -> Result<u16, String> {
+        self.spawn_backend(app_handle)
+    }
+
+    /// Allocate a port, launch the sidecar, wire up log readers and wait for it
+    /// to become healthy. Shared by the initial start and the restart path.
+    fn spawn_backend(&mut self, app_handle: &tauri::AppHandle) -> Result<u16, String> {
+        // A fresh spawn means we are live again; clear any pending-shutdown flag
+        // so a reset-driven restart keeps being supervised.
+        self.shutting_down.store(false, Ordering::SeqCst);
+
+        // Reap reader threads from any previous run before starting fresh ones.
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+
+        let port = match self.port_override {
+            Some(port) => port,
+            None => Self::find_available_port()?,
+        };
         self.port = port;
 
         let backend_exe = Self::get_backend_path(app_handle)?;
-        let app_data_dir = app_handle.path()
-            .app_data_dir()
-            .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+        let app_data_dir = self.resolve_app_data_dir(app_handle)?;
 
         // Ensure app data directory exists for persistent storage
         std::fs::create_dir_all(&app_data_dir)
             .map_err(|e| format!("Failed to create app data dir: {}", e))?;
 
+        // Point the rotating log at this install's logs directory.
+        {
+            let mut log = self.log.lock().map_err(|e| format!("Log lock error: {}", e))?;
+            log.dir = app_data_dir.join("logs");
+        }
+
         // Start backend with environment variables for configuration
-        let child = Command::new(&backend_exe)
+        let mut command = Command::new(&backend_exe);
+        command
             .env("SUZENT_PORT", port.to_string())
-            .env("SUZENT_HOST", "127.0.0.1")
+            .env("SUZENT_HOST", &self.host)
             .env("SUZENT_APP_DATA", &app_data_dir)
             .env("CHATS_DB_PATH", app_data_dir.join("chats.db"))
             .env("LANCEDB_URI", app_data_dir.join("memory"))
             .env("SANDBOX_DATA_PATH", app_data_dir.join("sandbox-data"))
             .env("SKILLS_DIR", app_data_dir.join("skills"))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // Hand the sidecar the loopback control channel so it can drive the window.
+        if let Some((control_port, token)) = &self.control {
+            command
+                .env("SUZENT_CONTROL_PORT", control_port.to_string())
+                .env("SUZENT_CONTROL_TOKEN", token);
+        }
+
+        // Put the child in its own process group so the Windows graceful-shutdown
+        // path can deliver a CTRL_BREAK_EVENT to it (see `signal_terminate`).
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+            command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+        }
+
+        let mut child = command
             .spawn()
             .map_err(|e| format!("Failed to start backend: {}", e))?;
 
+        if let Some(stdout) = child.stdout.take() {
+            self.readers.push(self.spawn_reader("stdout", stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            self.readers.push(self.spawn_reader("stderr", stderr));
+        }
+
         self.child = Some(child);
-        self.wait_for_backend()?;
+        self.wait_for_backend(app_handle)
+            .map_err(|e| e.to_string())?;
 
         Ok(port)
     }
 
-    /// Poll the backend health endpoint until it responds or timeout.
-    fn wait_for_backend(&self) -> Result<(), String> {
-        let url = format!("http://127.0.0.1:{}/api/config", self.port);
+    /// Spawn a thread that streams one of the child's pipes into the rotating log.
+    /// The thread exits on its own when the pipe closes (i.e. the child dies).
+    fn spawn_reader<R>(&self, stream: &'static str, pipe: R) -> JoinHandle<()>
+    where
+        R: std::io::Read + Send + 'static,
+    {
+        let log = Arc::clone(&self.log);
+        thread::spawn(move || {
+            let reader = BufReader::new(pipe);
+            for line in reader.lines().map_while(Result::ok) {
+                if let Ok(mut log) = log.lock() {
+                    log.write_line(stream, &line);
+                }
+            }
+        })
+    }
+
+    /// Poll the backend readiness endpoint until it reports ready, the child
+    /// exits, or the deadline passes.
+    ///
+    /// Each loop first checks liveness via `try_wait()` so a crashed child aborts
+    /// immediately instead of burning the whole timeout, then probes the health
+    /// path. The interval backs off exponentially up to the configured maximum,
+    /// and `backend-starting` events carry the attempt count for a splash screen.
+    fn wait_for_backend(
+        &mut self,
+        app_handle: &tauri::AppHandle,
+    ) -> Result<(), BackendStartError> {
+        let url = format!(
+            "http://{}:{}{}",
+            self.host, self.port, self.readiness.health_path
+        );
         let client = reqwest::blocking::Client::builder()
             .timeout(Duration::from_secs(2))
             .build()
-            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+            .map_err(|_| BackendStartError::ConnectionRefused)?;
 
-        // 60 attempts * 500ms = 30 seconds timeout
-        for attempt in 1..=60 {
-            thread::sleep(Duration::from_millis(500));
+        let deadline = Instant::now() + self.readiness.timeout;
+        let mut interval = self.readiness.initial_interval;
+        let mut attempt = 0u32;
+        // Track whether we ever reached the port, to tell "refused" from "timeout".
+        let mut connected = false;
 
-            if let Ok(resp) = client.get(&url).send() {
-                // Accept success or 404 (endpoint exists but might not have data yet)
-                if resp.status().is_success() || resp.status().as_u16() == 404 {
-                    println!("Backend ready after {} attempts", attempt);
-                    return Ok(());
+        loop {
+            // Liveness: a child that has already exited will never become ready.
+            if let Some(child) = self.child.as_mut() {
+                if matches!(child.try_wait(), Ok(Some(_))) {
+                    return Err(BackendStartError::ProcessExited);
                 }
             }
+
+            attempt += 1;
+            let _ = app_handle.emit("backend-starting", attempt);
+
+            match client.get(&url).send() {
+                Ok(resp) => {
+                    connected = true;
+                    if resp.status().is_success() {
+                        println!("Backend ready after {} attempts", attempt);
+                        return Ok(());
+                    }
+                }
+                Err(e) if !e.is_connect() => {
+                    // A non-connection error (e.g. request timeout) still means the
+                    // port answered, so don't report it as connection-refused.
+                    connected = true;
+                }
+                Err(_) => {}
+            }
+
+            if Instant::now() >= deadline {
+                return Err(if connected {
+                    BackendStartError::Timeout
+                } else {
+                    BackendStartError::ConnectionRefused
+                });
+            }
+
+            thread::sleep(interval);
+            interval = (interval * 2).min(self.readiness.max_interval);
         }
+    }
 
-        Err("Backend failed to start within 30 seconds".to_string())
+    /// True if the sidecar has been launched and has since exited on its own.
+    /// Reaps the child via `try_wait()` so the supervisor reacts to crashes.
+    pub fn has_exited(&mut self) -> bool {
+        match self.child.as_mut() {
+            Some(child) => matches!(child.try_wait(), Ok(Some(_))),
+            None => false,
+        }
+    }
+
+    /// True once `stop()` has begun, so the supervisor stops restarting.
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    /// Return the last `lines` lines written to the backend log files.
+    pub fn tail_logs(&self, lines: usize) -> Vec<String> {
+        match self.log.lock() {
+            Ok(log) => log.tail(lines),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// Delete the managed stores selected by `scope`, recreating the directory
+    /// ones empty so the backend can reseed them on its next start.
+    ///
+    /// Expects the caller to have stopped the sidecar first; deleting these files
+    /// while the backend holds them open is exactly the corruption `stop()` avoids.
+    /// Returns a human-readable summary of what was removed.
+    pub fn reset_data(
+        &self,
+        app_handle: &tauri::AppHandle,
+        scope: ResetScope,
+    ) -> Result<Vec<String>, String> {
+        let app_data_dir = self.resolve_app_data_dir(app_handle)?;
+
+        // (relative store, is-directory) — the file store is recreated lazily by
+        // the backend, directory stores are recreated empty here.
+        let stores = [
+            (ResetScope::Chats, "chats.db", false),
+            (ResetScope::Memory, "memory", true),
+            (ResetScope::Sandbox, "sandbox-data", true),
+            (ResetScope::Skills, "skills", true),
+        ];
+
+        let mut summary = Vec::new();
+        for (store_scope, name, is_dir) in stores {
+            if scope != ResetScope::All && scope != store_scope {
+                continue;
+            }
+            let path = app_data_dir.join(name);
+            if !path.exists() {
+                continue;
+            }
+            let removed = if is_dir {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            };
+            removed.map_err(|e| format!("Failed to remove {}: {}", name, e))?;
+            if is_dir {
+                std::fs::create_dir_all(&path)
+                    .map_err(|e| format!("Failed to recreate {}: {}", name, e))?;
+            }
+            summary.push(name.to_string());
+        }
+
+        Ok(summary)
     }
 
     /// Stop the backend process gracefully.
+    ///
+    /// The sidecar owns `chats.db`, the LanceDB `memory` dir and sandbox data, so
+    /// a hard kill can leave them mid-write. Signal a clean exit first, give it a
+    /// grace period to flush, and only then escalate to `kill()`.
     pub fn stop(&mut self) {
+        self.shutting_down.store(true, Ordering::SeqCst);
         if let Some(mut child) = self.child.take() {
-            let _ = child.kill();
+            Self::terminate_gracefully(&mut child);
+        }
+        // The child is gone now, so the pipes are closed; drain the readers.
+        for reader in self.readers.drain(..) {
+            let _ = reader.join();
+        }
+    }
+
+    /// Ask the child to exit cleanly, poll for the grace period, then force-kill.
+    fn terminate_gracefully(child: &mut Child) {
+        if let Ok(Some(_)) = child.try_wait() {
+            return; // Already gone.
+        }
+
+        Self::signal_terminate(child);
+
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+
+        // Did not exit in time; escalate.
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+
+    /// Signal the child to shut down cleanly without killing it outright.
+    #[cfg(unix)]
+    fn signal_terminate(child: &Child) {
+        unsafe {
+            libc::kill(child.id() as libc::pid_t, libc::SIGTERM);
+        }
+    }
+
+    /// Signal the child on Windows via a CTRL_BREAK_EVENT to its process group.
+    ///
+    /// The child is spawned with `CREATE_NEW_PROCESS_GROUP` (see `spawn_backend`)
+    /// so the event targets a real group id (`child.id()`). Note that a release
+    /// build has no attached console (`windows_subsystem = "windows"`); if the
+    /// event can't be delivered the child simply won't exit in time and
+    /// `terminate_gracefully` degrades to a hard `kill()`.
+    #[cfg(windows)]
+    fn signal_terminate(child: &Child) {
+        // CTRL_BREAK_EVENT == 1; delivered to the child's own process group.
+        extern "system" {
+            fn GenerateConsoleCtrlEvent(dw_ctrl_event: u32, dw_process_group_id: u32) -> i32;
+        }
+        unsafe {
+            GenerateConsoleCtrlEvent(1, child.id());
         }
     }
 }
@@ -0,0 +1,141 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+/// A control request sent by the Python backend over the localhost channel.
+///
+/// Every request must carry the per-launch `token`; the backend receives it as
+/// `SUZENT_CONTROL_TOKEN` so no other local process can steer the window.
+#[derive(Deserialize)]
+struct ControlRequest {
+    token: String,
+    action: String,
+    #[serde(default)]
+    title: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// How long a connection may stall before its read is abandoned.
+///
+/// A well-behaved backend sends its JSON line immediately; the timeout stops a
+/// local process from holding the control path open without ever writing.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Generate a 128-bit secret token for this launch, hex-encoded.
+///
+/// Used as a shared secret between the GUI and the sidecar. The bytes come from
+/// the OS CSPRNG so a hostile local process can't predict or forge the token;
+/// binding the listener to loopback keeps the channel off the network.
+fn generate_token() -> String {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).expect("OS CSPRNG unavailable");
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A reverse-control channel that lets the sidecar drive the native window.
+///
+/// The GUI owns the listener; the backend connects to `127.0.0.1:<port>` and
+/// sends one JSON line per request. Both the port and the secret are handed to
+/// the child via environment variables so it can reach back.
+pub struct ControlChannel {
+    pub port: u16,
+    pub token: String,
+}
+
+impl ControlChannel {
+    /// Bind a loopback listener and spawn the accept loop.
+    ///
+    /// The listener uses an OS-assigned port (`:0`) like [`BackendProcess`], so
+    /// it never collides with the sidecar's HTTP port.
+    pub fn start(app_handle: AppHandle) -> Result<Self, String> {
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .map_err(|e| format!("Failed to bind control channel: {}", e))?;
+        let port = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read control addr: {}", e))?
+            .port();
+        let token = generate_token();
+
+        let accept_token = token.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                // Handle each connection on its own thread so a slow or silent
+                // client can't stall the accept loop for everyone else.
+                let app_handle = app_handle.clone();
+                let token = accept_token.clone();
+                thread::spawn(move || handle_connection(&app_handle, &token, stream));
+            }
+        });
+
+        Ok(ControlChannel { port, token })
+    }
+}
+
+/// Read and dispatch a single request from `stream`.
+///
+/// Answers malformed or unauthenticated requests with a bare `{"ok":false}` and
+/// no reason; a steering primitive should fail closed rather than report why it
+/// refused.
+fn handle_connection(app_handle: &AppHandle, token: &str, stream: TcpStream) {
+    // Bound the read so a client that connects but never sends a line can't hold
+    // the handler open indefinitely.
+    let _ = stream.set_read_timeout(Some(READ_TIMEOUT));
+    let mut reader = BufReader::new(match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    });
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+
+    let Ok(req) = serde_json::from_str::<ControlRequest>(line.trim()) else {
+        respond(stream, false);
+        return;
+    };
+    if req.token != token {
+        respond(stream, false);
+        return;
+    }
+
+    let handled = dispatch(app_handle, &req);
+    respond(stream, handled);
+}
+
+/// Map an authenticated request onto the window / notification APIs.
+fn dispatch(app_handle: &AppHandle, req: &ControlRequest) -> bool {
+    match req.action.as_str() {
+        "show" => app_handle
+            .get_webview_window("main")
+            .map(|w| w.show().is_ok())
+            .unwrap_or(false),
+        "hide" => app_handle
+            .get_webview_window("main")
+            .map(|w| w.hide().is_ok())
+            .unwrap_or(false),
+        "focus" => app_handle
+            .get_webview_window("main")
+            .map(|w| w.set_focus().is_ok())
+            .unwrap_or(false),
+        "notify" => app_handle
+            .notification()
+            .builder()
+            .title(req.title.clone().unwrap_or_else(|| "Suzent".to_string()))
+            .body(req.body.clone().unwrap_or_default())
+            .show()
+            .is_ok(),
+        _ => false,
+    }
+}
+
+/// Send a one-line JSON acknowledgement so the backend can tell success from refusal.
+fn respond(mut stream: TcpStream, ok: bool) {
+    let _ = writeln!(stream, "{{\"ok\":{}}}", ok);
+}
@@ -2,13 +2,56 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod backend;
+mod control;
 
-use backend::BackendProcess;
-use tauri::{Manager, State};
-use std::sync::Mutex;
+use backend::{BackendProcess, ResetScope};
+use control::ControlChannel;
+use clap::Parser;
+use tauri::{Emitter, Manager, State};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Command-line overrides for the launcher.
+///
+/// These take precedence over the build-mode defaults in `get_backend_config`,
+/// so the same binary can drive a GUI, pin a fixed port/host, relocate the data
+/// directory, or run headless on a server.
+#[derive(Parser, Debug, Clone)]
+#[command(name = "suzent", about = "Suzent desktop launcher")]
+struct Cli {
+    /// Fixed backend port (skips automatic port discovery).
+    #[arg(long)]
+    port: Option<u16>,
+
+    /// Host the backend binds to (default 127.0.0.1).
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Override the app data directory used for the backend's stores.
+    #[arg(long)]
+    data_dir: Option<PathBuf>,
+
+    /// Run without a window, keeping only the sidecar alive (server/CI use).
+    #[arg(long = "no-window")]
+    no_window: bool,
+}
+
+/// How often the supervisor polls the sidecar for an unexpected exit.
+const SUPERVISOR_POLL: Duration = Duration::from_secs(2);
+/// Maximum consecutive restart attempts before the supervisor gives up.
+const SUPERVISOR_MAX_RETRIES: u32 = 5;
+/// How long a restarted sidecar must stay up before it counts as stable and the
+/// crash counter is cleared. Shorter-lived runs are treated as a crash loop.
+const SUPERVISOR_STABILITY_WINDOW: Duration = Duration::from_secs(30);
 
 struct AppState {
     backend: Mutex<BackendProcess>,
+    /// Monotonic token identifying the live supervisor. Starting a new supervisor
+    /// bumps it; any older supervisor notices its generation is stale and exits,
+    /// so a reset never leaves two supervisors racing to restart the sidecar.
+    supervisor_gen: Arc<AtomicU64>,
 }
 
 #[tauri::command]
@@ -18,6 +61,42 @@ fn get_backend_port(state: State<AppState>) -> Result<u16, String> {
     Ok(backend.port)
 }
 
+#[tauri::command]
+fn get_backend_logs(state: State<AppState>, lines: usize) -> Result<Vec<String>, String> {
+    let backend = state.backend.lock()
+        .map_err(|e| format!("Lock error: {}", e))?;
+    Ok(backend.tail_logs(lines))
+}
+
+/// Reset the app to a clean state by wiping the selected managed data stores.
+///
+/// Stops the sidecar gracefully so it releases `chats.db` and the LanceDB files,
+/// deletes the chosen subtrees, then restarts the backend (re-injecting the new
+/// port and resuming supervision). Returns the list of stores that were removed.
+#[tauri::command]
+fn reset_app_data(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    scope: ResetScope,
+) -> Result<Vec<String>, String> {
+    let summary = {
+        let mut backend = state.backend.lock()
+            .map_err(|e| format!("Lock error: {}", e))?;
+        backend.stop();
+        let summary = backend.reset_data(&app_handle, scope)?;
+        let port = backend.restart(&app_handle)?;
+        if let Some(window) = app_handle.get_webview_window("main") {
+            let _ = inject_backend_port(&window, port);
+        }
+        let _ = app_handle.emit("backend-restarted", port);
+        summary
+    };
+    // Start a fresh supervisor for the restarted sidecar; bumping the
+    // generation makes the old one stand down.
+    start_supervisor(app_handle);
+    Ok(summary)
+}
+
 /// Inject the backend port into the frontend window via JavaScript global variable.
 /// This allows the frontend to dynamically connect to the backend regardless of port.
 fn inject_backend_port(window: &tauri::WebviewWindow, port: u16) -> Result<(), String> {
@@ -25,25 +104,160 @@ fn inject_backend_port(window: &tauri::WebviewWindow, port: u16) -> Result<(), S
         .map_err(|e| format!("Failed to inject port: {}", e))
 }
 
+/// Watch the sidecar and restart it if it exits unexpectedly.
+///
+/// Polls `try_wait()` via `BackendProcess::has_exited`; on a crash it re-spawns
+/// via `BackendProcess::restart` (exponential backoff, capped retries), updates the shared
+/// `AppState.backend.port`, re-injects the port into the window and emits a
+/// `backend-restarted` event so the frontend can reconnect.
+fn start_supervisor(app_handle: tauri::AppHandle) {
+    // Claim the next generation; any supervisor started earlier is now stale.
+    let gen = app_handle
+        .state::<AppState>()
+        .supervisor_gen
+        .fetch_add(1, Ordering::SeqCst)
+        + 1;
+    std::thread::spawn(move || {
+        let mut failures: u32 = 0;
+        // When the last restart happened, so we can tell a stable run from a
+        // crash loop; `None` means the backend hasn't been restarted yet.
+        let mut last_restart: Option<Instant> = None;
+        loop {
+            std::thread::sleep(SUPERVISOR_POLL);
+            let state = app_handle.state::<AppState>();
+
+            // A newer supervisor has taken over (e.g. after a reset); stand down.
+            if state.supervisor_gen.load(Ordering::SeqCst) != gen {
+                break;
+            }
+
+            let exited = {
+                let mut backend = match state.backend.lock() {
+                    Ok(b) => b,
+                    Err(_) => continue,
+                };
+                if backend.is_shutting_down() {
+                    break;
+                }
+                backend.has_exited()
+            };
+            if !exited {
+                // Only clear the crash counter once the sidecar has stayed up
+                // for the stability window; a backend that starts cleanly and
+                // dies seconds later must keep counting toward the ceiling.
+                if let Some(started) = last_restart {
+                    if started.elapsed() >= SUPERVISOR_STABILITY_WINDOW {
+                        failures = 0;
+                        last_restart = None;
+                    }
+                }
+                continue;
+            }
+
+            failures += 1;
+            if failures > SUPERVISOR_MAX_RETRIES {
+                eprintln!(
+                    "Backend exceeded max restart retries ({}); giving up",
+                    SUPERVISOR_MAX_RETRIES
+                );
+                break;
+            }
+
+            // Exponential backoff (2s, 4s, 8s, ...) before the next attempt.
+            std::thread::sleep(Duration::from_secs(2u64.pow(failures)));
+
+            if state.supervisor_gen.load(Ordering::SeqCst) != gen {
+                break;
+            }
+            let restart = {
+                let mut backend = match state.backend.lock() {
+                    Ok(b) => b,
+                    Err(_) => continue,
+                };
+                if backend.is_shutting_down() {
+                    break;
+                }
+                backend.restart(&app_handle)
+            };
+
+            match restart {
+                Ok(port) => {
+                    // Don't reset `failures` here: a spawn that passes readiness
+                    // but crashes again shortly is still a crash loop. The counter
+                    // clears only after the stability window above.
+                    last_restart = Some(Instant::now());
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        let _ = inject_backend_port(&window, port);
+                    }
+                    let _ = app_handle.emit("backend-restarted", port);
+                    println!("Backend restarted on port {}", port);
+                }
+                Err(e) => eprintln!("Backend restart failed: {}", e),
+            }
+        }
+    });
+}
+
 fn main() {
+    let cli = Cli::parse();
     tauri::Builder::default()
-        .setup(|app| {
-            let window = app.get_webview_window("main")
-                .ok_or("Failed to get main window")?;
-
-            // Determine port and backend process based on build mode
-            let (port, backend) = get_backend_config(app)?;
+        // Register single-instance first (per the plugin's guidance) so a second
+        // launch focuses the running window instead of spawning a rival backend
+        // that would write the same SQLite/LanceDB files concurrently.
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            // Forward the secondary launch's arguments to the frontend; the
+            // primary instance owns the sidecar and handles any deep link.
+            let _ = app.emit("single-instance", argv);
+        }))
+        .plugin(tauri_plugin_notification::init())
+        .setup(move |app| {
+            // Stand up the reverse-control channel so the sidecar can drive the
+            // window, then hand its port/secret to the backend before it starts.
+            let control = ControlChannel::start(app.handle().clone())?;
 
-            inject_backend_port(&window, port)?;
+            // Determine port and backend process based on build mode and CLI.
+            let (port, backend) = get_backend_config(app, &control, &cli)?;
             println!("Backend configured on port {}", port);
 
+            // Headless mode never constructs a webview (that would require a
+            // display), so the sidecar can run on a server or in CI; otherwise
+            // build the main window and inject the backend port.
+            if !cli.no_window {
+                let window = tauri::WebviewWindowBuilder::new(
+                    app,
+                    "main",
+                    tauri::WebviewUrl::default(),
+                )
+                .title("Suzent")
+                .build()
+                .map_err(|e| format!("Failed to create main window: {}", e))?;
+                inject_backend_port(&window, port)?;
+            }
+
             app.manage(AppState {
                 backend: Mutex::new(backend),
+                supervisor_gen: Arc::new(AtomicU64::new(0)),
             });
 
+            start_supervisor(app.handle().clone());
+
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![get_backend_port])
+        .on_window_event(|window, event| {
+            // Run the graceful backend shutdown before the process tree is torn down.
+            if let tauri::WindowEvent::CloseRequested { .. } = event {
+                if let Some(state) = window.app_handle().try_state::<AppState>() {
+                    if let Ok(mut backend) = state.backend.lock() {
+                        backend.stop();
+                    }
+                }
+            }
+        })
+        .invoke_handler(tauri::generate_handler![get_backend_port, get_backend_logs, reset_app_data])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -52,16 +266,27 @@ fn main() {
 /// - Release: Starts bundled backend and returns its dynamically allocated port
 /// - Debug: Returns default port 8000 (expects manually-run backend)
 #[cfg(not(debug_assertions))]
-fn get_backend_config(app: &tauri::App) -> Result<(u16, BackendProcess), String> {
+fn get_backend_config(
+    app: &tauri::App,
+    control: &ControlChannel,
+    cli: &Cli,
+) -> Result<(u16, BackendProcess), String> {
     let mut backend = BackendProcess::new();
+    backend.set_control_channel(control.port, control.token.clone());
+    backend.set_launch_options(cli.port, cli.host.clone(), cli.data_dir.clone());
     let port = backend.start(&app.handle())?;
     Ok((port, backend))
 }
 
 #[cfg(debug_assertions)]
-fn get_backend_config(_app: &tauri::App) -> Result<(u16, BackendProcess), String> {
+fn get_backend_config(
+    _app: &tauri::App,
+    _control: &ControlChannel,
+    cli: &Cli,
+) -> Result<(u16, BackendProcess), String> {
+    let port = cli.port.unwrap_or(8000);
     println!("Development mode: Please start backend manually with:");
     println!("  python src/suzent/server.py");
-    println!("Expected backend URL: http://localhost:8000");
-    Ok((8000, BackendProcess::new()))
+    println!("Expected backend URL: http://localhost:{}", port);
+    Ok((port, BackendProcess::new()))
 }